@@ -95,8 +95,9 @@ use std::{
     collections::{HashMap, HashSet},
     fs,
     path::Path,
+    process::Command,
 };
-use toml_edit::{Array, Document, Item};
+use toml_edit::{Array, Document, Item, Table};
 
 /// Loads a recipe, reduces it with [`reduce_recipe`] and
 /// saves the reduces recipe to a file.
@@ -113,51 +114,268 @@ use toml_edit::{Array, Document, Item};
 pub fn reduce_recipe_file<P: AsRef<Path>>(
     input_path: &P,
     output_path: &P,
-    target_member: &str,
+    target_members: &[String],
+    manifest_path: Option<&Path>,
 ) -> Result<()> {
     let recipe = load_recipe(input_path)?;
 
-    let reduced = reduce_recipe(&recipe, target_member)?;
+    let reduced = reduce_recipe(&recipe, target_members, manifest_path)?;
 
     let out = serde_json::to_string(&reduced).context("failed to serialize reduced recipe")?;
     save_recipe(&out, output_path)
 }
 
+/// Loads a recipe and reports what [`reduce_recipe`] would prune without
+/// writing any output. Used by the `--report` (dry-run) mode.
+///
+/// # Errors
+/// - Could not load the file
+/// - Could not reduce the recipe (see [`reduce_recipe`])
+pub fn reduce_recipe_report_file<P: AsRef<Path>>(
+    input_path: &P,
+    target_members: &[String],
+    manifest_path: Option<&Path>,
+) -> Result<ReductionReport> {
+    let recipe = load_recipe(input_path)?;
+    reduce_recipe_report(&recipe, target_members, manifest_path)
+}
+
 /// Reduce a workspace recipe and return it as a JSON string
 ///
 /// - Finds the root workspace members that the recipe should be reduced to
 /// - Calculates dependencies and transitive dependencies of the root members
 /// - Filters manifest and lockfile
 ///
+/// When `manifest_path` points at the real workspace root `Cargo.toml`, the
+/// member/dependency graph is taken from `cargo metadata` instead of being
+/// reconstructed from the recipe's embedded manifests. That authoritative graph
+/// only contains the optional dependencies whose features are actually enabled,
+/// which pure TOML parsing cannot tell. Passing `None` keeps the recipe-only reconstruction, which is the
+/// default for environments where the source tree isn't present.
+///
 /// # Errors
 /// - Could not get root manifest
 /// - Could not find workspace members or workspace dependencies
 /// - Could not build workspace member/dependencies graph
 /// - Could not filter manifest
 /// - Could not filter lockfile
-pub fn reduce_recipe(recipe: &Recipe, target_member: &str) -> Result<Recipe> {
+pub fn reduce_recipe(
+    recipe: &Recipe,
+    target_members: &[String],
+    manifest_path: Option<&Path>,
+) -> Result<Recipe> {
+    Ok(reduce(recipe, target_members, manifest_path)?.recipe)
+}
+
+/// Outcome of a reduction: the reduced recipe together with the workspace
+/// dependency sets used to drive it.
+///
+/// [`reduce_recipe_report`] reads `all_ws_deps`/`keep_ws_deps` from here to
+/// report which shared workspace dependencies were dropped. The root manifest's
+/// `[workspace.dependencies]` table is never rewritten by the reduction, so that
+/// information cannot be recovered by diffing the manifests; it only lives in the
+/// computed keep set.
+struct Reduction {
+    recipe: Recipe,
+    all_ws_deps: HashSet<String>,
+    keep_ws_deps: HashSet<String>,
+}
+
+/// Run the reduction and return the reduced recipe alongside the workspace
+/// dependency keep set, shared by [`reduce_recipe`] and [`reduce_recipe_report`].
+fn reduce(
+    recipe: &Recipe,
+    target_members: &[String],
+    manifest_path: Option<&Path>,
+) -> Result<Reduction> {
     let root_manifest = get_root_manifest(recipe)?;
 
     let all_members = get_workspace_members(recipe);
 
     let all_ws_deps = get_workspace_deps(root_manifest)?;
 
-    let (members_graph, ws_deps_graph) = build_dependencies(recipe, &all_members, &all_ws_deps);
+    let (members_graph, ws_deps_graph) = match manifest_path {
+        Some(path) => build_dependencies_from_metadata(path, &all_members, &all_ws_deps)?,
+        None => build_dependencies(recipe, &all_members, &all_ws_deps),
+    };
 
-    let keep_members = compute_transitive_deps(target_member, &members_graph);
+    let keep_members = compute_transitive_deps(target_members, &members_graph);
 
-    let keep_ws_deps = compute_transitive_deps(target_member, &ws_deps_graph);
+    let keep_ws_deps = compute_transitive_deps(target_members, &ws_deps_graph);
 
     let mut reduced = recipe.clone();
-    filter_root_members(&mut reduced, target_member)?;
+    filter_root_members(&mut reduced, target_members)?;
 
     filter_manifests(&mut reduced, &keep_members);
 
-    filter_lockfile(&mut reduced, &all_members, &keep_members)?;
+    filter_lockfile(&mut reduced, &keep_members, &keep_ws_deps)?;
+
+    Ok(Reduction {
+        recipe: reduced,
+        all_ws_deps,
+        keep_ws_deps,
+    })
+}
+
+/// A summary of what a reduction removed versus kept, produced by
+/// [`reduce_recipe_report`] for the `--report` (dry-run) mode.
+///
+/// It carries the kept and removed workspace members, shared workspace
+/// dependencies and lockfile packages, together with the before/after byte
+/// sizes of the embedded manifests and lockfile.
+pub struct ReductionReport {
+    /// Workspace members kept in the reduced recipe.
+    pub kept_members: Vec<String>,
+    /// Workspace members removed from the recipe.
+    pub removed_members: Vec<String>,
+    /// Shared `[workspace.dependencies]` entries that the kept members depend on.
+    pub kept_ws_deps: Vec<String>,
+    /// Shared `[workspace.dependencies]` entries that no kept member depends on.
+    /// The entries themselves stay in the root manifest; only their lockfile
+    /// packages are pruned.
+    pub removed_ws_deps: Vec<String>,
+    /// Lockfile packages (`name version`) kept in the reduced recipe.
+    pub kept_packages: Vec<String>,
+    /// Lockfile packages (`name version`) removed from the recipe.
+    pub removed_packages: Vec<String>,
+    /// Total bytes of all manifest contents before reduction.
+    pub manifest_bytes_before: usize,
+    /// Total bytes of all manifest contents after reduction.
+    pub manifest_bytes_after: usize,
+    /// Bytes of the lockfile before reduction.
+    pub lockfile_bytes_before: usize,
+    /// Bytes of the lockfile after reduction.
+    pub lockfile_bytes_after: usize,
+}
+
+/// Reduce a recipe and report what was pruned without writing any output.
+///
+/// This runs the exact same reduction as [`reduce_recipe`]. The member and
+/// lockfile sections are derived by diffing the original recipe against the
+/// reduced one, so they reflect precisely what a real run would have written.
+/// The workspace-dependency section is derived from the computed keep set
+/// instead: `[workspace.dependencies]` is never rewritten by the reduction, so
+/// the dropped shared deps only exist in that set, not in the manifests.
+///
+/// # Errors
+/// - Could not reduce the recipe (see [`reduce_recipe`])
+pub fn reduce_recipe_report(
+    recipe: &Recipe,
+    target_members: &[String],
+    manifest_path: Option<&Path>,
+) -> Result<ReductionReport> {
+    let Reduction {
+        recipe: reduced,
+        all_ws_deps,
+        keep_ws_deps,
+    } = reduce(recipe, target_members, manifest_path)?;
+
+    let before_members = get_workspace_members(recipe);
+    let after_members = get_workspace_members(&reduced);
+
+    let kept_ws_deps = &all_ws_deps & &keep_ws_deps;
+    let removed_ws_deps = &all_ws_deps - &keep_ws_deps;
+
+    let before_packages = lockfile_packages(recipe);
+    let after_packages = lockfile_packages(&reduced);
+
+    Ok(ReductionReport {
+        kept_members: sorted(&after_members),
+        removed_members: sorted(&(&before_members - &after_members)),
+        kept_ws_deps: sorted(&kept_ws_deps),
+        removed_ws_deps: sorted(&removed_ws_deps),
+        kept_packages: sorted(&after_packages),
+        removed_packages: sorted(&(&before_packages - &after_packages)),
+        manifest_bytes_before: manifest_bytes(recipe),
+        manifest_bytes_after: manifest_bytes(&reduced),
+        lockfile_bytes_before: lockfile_bytes(recipe),
+        lockfile_bytes_after: lockfile_bytes(&reduced),
+    })
+}
+
+impl std::fmt::Display for ReductionReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "workspace members: {} kept, {} removed",
+            self.kept_members.len(),
+            self.removed_members.len()
+        )?;
+        for name in &self.removed_members {
+            writeln!(f, "  - {name}")?;
+        }
+        writeln!(
+            f,
+            "workspace dependencies: {} kept, {} removed",
+            self.kept_ws_deps.len(),
+            self.removed_ws_deps.len()
+        )?;
+        for name in &self.removed_ws_deps {
+            writeln!(f, "  - {name}")?;
+        }
+        writeln!(
+            f,
+            "lockfile packages: {} kept, {} removed",
+            self.kept_packages.len(),
+            self.removed_packages.len()
+        )?;
+        for name in &self.removed_packages {
+            writeln!(f, "  - {name}")?;
+        }
+        writeln!(
+            f,
+            "manifest bytes: {} -> {}",
+            self.manifest_bytes_before, self.manifest_bytes_after
+        )?;
+        write!(
+            f,
+            "lockfile bytes: {} -> {}",
+            self.lockfile_bytes_before, self.lockfile_bytes_after
+        )
+    }
+}
+
+/// Collect the lockfile packages as `name version` identifiers.
+fn lockfile_packages(recipe: &Recipe) -> HashSet<String> {
+    let Some(lock_txt) = &recipe.skeleton.lock_file else {
+        return HashSet::new();
+    };
+    let Ok(doc) = lock_txt.parse::<Document<String>>() else {
+        return HashSet::new();
+    };
+    let Some(Item::ArrayOfTables(array)) = doc.get("package") else {
+        return HashSet::new();
+    };
+    array
+        .iter()
+        .filter_map(|pkg| {
+            let name = pkg.get("name").and_then(Item::as_str)?;
+            let version = pkg.get("version").and_then(Item::as_str).unwrap_or("");
+            Some(format!("{name} {version}").trim().to_string())
+        })
+        .collect()
+}
+
+/// Total bytes of all embedded manifest contents.
+fn manifest_bytes(recipe: &Recipe) -> usize {
+    recipe
+        .skeleton
+        .manifests
+        .iter()
+        .map(|m| m.contents.len())
+        .sum()
+}
 
-    filter_lockfile(&mut reduced, &all_ws_deps, &keep_ws_deps)?;
+/// Bytes of the embedded lockfile, or zero if there is none.
+fn lockfile_bytes(recipe: &Recipe) -> usize {
+    recipe.skeleton.lock_file.as_ref().map_or(0, String::len)
+}
 
-    Ok(reduced)
+/// Return the set as a sorted vector for stable reporting.
+fn sorted(set: &HashSet<String>) -> Vec<String> {
+    let mut out: Vec<String> = set.iter().cloned().collect();
+    out.sort();
+    out
 }
 
 /// Get root manifest
@@ -203,6 +421,10 @@ fn get_workspace_deps(root: &Manifest) -> Result<HashSet<String>> {
         .collect())
 }
 
+/// Dependency table keys inspected on every manifest, both at the top level
+/// and inside each `[target.*]` subtable.
+const DEP_TABLE_KEYS: [&str; 3] = ["dependencies", "dev-dependencies", "build-dependencies"];
+
 /// Build workspace dependency map
 fn build_dependencies(
     recipe: &Recipe,
@@ -217,24 +439,14 @@ fn build_dependencies(
 
     for manifest in &recipe.skeleton.manifests {
         if let Some(name) = extract_crate_name(manifest) {
-            let mut members = HashSet::new();
-            let mut ws_deps = HashSet::new();
             let doc: Document<String> = match manifest.contents.parse() {
                 Ok(d) => d,
                 Err(_) => continue,
             };
-            for key in ["dependencies", "dev-dependencies"] {
-                if let Some(table) = doc.get(key).and_then(|v| v.as_table()) {
-                    for (dep_name, _) in table {
-                        if all_ws_members.contains(dep_name) {
-                            members.insert(dep_name.to_string());
-                        }
-                        if all_ws_dependencies.contains(dep_name) {
-                            ws_deps.insert(dep_name.to_string());
-                        }
-                    }
-                }
-            }
+
+            let (members, ws_deps) =
+                collect_manifest_deps(&doc, all_ws_members, all_ws_dependencies);
+
             members_graph.insert(name.clone(), members);
             ws_deps_graph.insert(name, ws_deps);
         }
@@ -243,13 +455,159 @@ fn build_dependencies(
     (members_graph, ws_deps_graph)
 }
 
-/// Compute all transitive dependencies of the given target member.
+/// Collect the workspace members and shared workspace dependencies referenced by
+/// a single manifest.
+///
+/// Every dependency table is inspected: the top-level `[dependencies]`,
+/// `[dev-dependencies]` and `[build-dependencies]`, as well as the same tables
+/// nested under each `[target.*]` platform cfg. A renamed dependency
+/// (`alias = { package = "bar", .. }`) pulls in the crate named by `package`,
+/// not the table key, mirroring how Cargo distinguishes the declared name from
+/// the real package name.
+fn collect_manifest_deps(
+    doc: &Document<String>,
+    all_ws_members: &HashSet<String>,
+    all_ws_dependencies: &HashSet<String>,
+) -> (HashSet<String>, HashSet<String>) {
+    let mut members = HashSet::new();
+    let mut ws_deps = HashSet::new();
+
+    let mut scan = |table: &Table| {
+        for (key, value) in table {
+            let dep_name = value
+                .as_table_like()
+                .and_then(|t| t.get("package"))
+                .and_then(Item::as_str)
+                .unwrap_or(key);
+            if all_ws_members.contains(dep_name) {
+                members.insert(dep_name.to_string());
+                // Members are also recorded in the workspace-dependency graph so
+                // the closure reaches shared deps pulled in through intermediate
+                // members (e.g. `foo` -> member `bar` -> ws dep `serde`).
+                ws_deps.insert(dep_name.to_string());
+            }
+            if all_ws_dependencies.contains(dep_name) {
+                ws_deps.insert(dep_name.to_string());
+            }
+        }
+    };
+
+    // Top-level `[dependencies]`, `[dev-dependencies]` and `[build-dependencies]`.
+    for key in DEP_TABLE_KEYS {
+        if let Some(table) = doc.get(key).and_then(Item::as_table) {
+            scan(table);
+        }
+    }
+
+    // Platform-specific tables such as `[target.'cfg(unix)'.dependencies]`.
+    if let Some(targets) = doc.get("target").and_then(Item::as_table) {
+        for (_, target) in targets {
+            let Some(target) = target.as_table() else {
+                continue;
+            };
+            for key in DEP_TABLE_KEYS {
+                if let Some(table) = target.get(key).and_then(Item::as_table) {
+                    scan(table);
+                }
+            }
+        }
+    }
+
+    (members, ws_deps)
+}
+
+/// Build the workspace dependency map from `cargo metadata`'s resolved graph.
+///
+/// Unlike [`build_dependencies`], which reconstructs the graph by hand-parsing
+/// each manifest, this shells out to `cargo metadata --format-version 1` against
+/// the real workspace and reads the authoritative `resolve.nodes` adjacency.
+/// That graph only contains the feature-gated optional dependencies that are
+/// actually enabled, whereas TOML parsing records every optional dependency.
+fn build_dependencies_from_metadata(
+    manifest_path: &Path,
+    all_ws_members: &HashSet<String>,
+    all_ws_dependencies: &HashSet<String>,
+) -> Result<(
+    HashMap<String, HashSet<String>>,
+    HashMap<String, HashSet<String>>,
+)> {
+    let output = Command::new("cargo")
+        .arg("metadata")
+        .arg("--format-version")
+        .arg("1")
+        .arg("--manifest-path")
+        .arg(manifest_path)
+        .output()
+        .context("failed to run `cargo metadata`")?;
+
+    if !output.status.success() {
+        anyhow::bail!(
+            "`cargo metadata` failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    let metadata: serde_json::Value = serde_json::from_slice(&output.stdout)
+        .context("failed to parse `cargo metadata` output")?;
+
+    // Map every resolved `PackageId` to its crate name.
+    let mut id_to_name: HashMap<&str, &str> = HashMap::new();
+    if let Some(packages) = metadata["packages"].as_array() {
+        for pkg in packages {
+            if let (Some(id), Some(name)) = (pkg["id"].as_str(), pkg["name"].as_str()) {
+                id_to_name.insert(id, name);
+            }
+        }
+    }
+
+    let nodes = metadata["resolve"]["nodes"]
+        .as_array()
+        .context("`cargo metadata` output has no resolve.nodes")?;
+
+    let mut members_graph = HashMap::new();
+    let mut ws_deps_graph = HashMap::new();
+
+    for node in nodes {
+        let Some(name) = node["id"].as_str().and_then(|id| id_to_name.get(id)) else {
+            continue;
+        };
+
+        let mut members = HashSet::new();
+        let mut ws_deps = HashSet::new();
+
+        if let Some(deps) = node["dependencies"].as_array() {
+            for dep in deps {
+                let Some(dep_name) = dep.as_str().and_then(|id| id_to_name.get(id)) else {
+                    continue;
+                };
+                let dep_name = (*dep_name).to_string();
+                if all_ws_members.contains(&dep_name) {
+                    // Members are also recorded in the workspace-dependency graph
+                    // so the transitive closure reaches shared deps pulled in
+                    // through intermediate members.
+                    members.insert(dep_name.clone());
+                    ws_deps.insert(dep_name.clone());
+                }
+                if all_ws_dependencies.contains(&dep_name) {
+                    ws_deps.insert(dep_name);
+                }
+            }
+        }
+
+        members_graph.insert((*name).to_string(), members);
+        ws_deps_graph.insert((*name).to_string(), ws_deps);
+    }
+
+    Ok((members_graph, ws_deps_graph))
+}
+
+/// Compute the union of the transitive dependencies of the given target members.
 fn compute_transitive_deps(
-    target: &str,
+    targets: &[String],
     deps: &HashMap<String, HashSet<String>>,
 ) -> HashSet<String> {
     let mut keep = HashSet::new();
-    let mut stack = vec![target.to_string()];
+    let mut stack: Vec<String> = targets.to_vec();
 
     while let Some(member) = stack.pop() {
         if keep.insert(member.clone())
@@ -261,8 +619,8 @@ fn compute_transitive_deps(
 
     keep
 }
-/// Filters the root manifest workspace members to keep ony the target member
-fn filter_root_members(recipe: &mut Recipe, target: &str) -> Result<()> {
+/// Filters the root manifest workspace members to keep only the target members
+fn filter_root_members(recipe: &mut Recipe, targets: &[String]) -> Result<()> {
     let root = get_root_manifest_mut(recipe)?;
 
     let doc: Document<String> = root
@@ -272,7 +630,9 @@ fn filter_root_members(recipe: &mut Recipe, target: &str) -> Result<()> {
     let mut doc = doc.into_mut();
 
     let mut arr = Array::new();
-    arr.push(target);
+    for target in targets {
+        arr.push(target.as_str());
+    }
     doc["workspace"]["members"] = arr.into();
 
     root.contents = doc.to_string();
@@ -292,28 +652,136 @@ fn filter_manifests(recipe: &mut Recipe, keep_members: &HashSet<String>) {
         .retain(|m| extract_crate_name(m).is_none_or(|name| keep_members.contains(&name)));
 }
 
-/// Filter lockfile to keep only relevant dependencies
+/// Filter the lockfile down to the packages actually reachable from the kept
+/// workspace members and workspace dependencies.
+///
+/// Rather than only dropping `[[package]]` entries whose name is a removed
+/// workspace member, this performs a reachability pass over the lockfile's own
+/// dependency graph: external (registry/git) crates that are pulled in solely
+/// by removed members are stripped as well. That is where the bulk of the
+/// promised build-time reduction comes from.
 fn filter_lockfile(
     recipe: &mut Recipe,
-    all_members: &HashSet<String>,
     keep_members: &HashSet<String>,
+    keep_ws_deps: &HashSet<String>,
 ) -> Result<()> {
-    if let Some(lock_txt) = &recipe.skeleton.lock_file {
-        let doc: Document<String> = lock_txt.parse()?;
-        let mut doc = doc.into_mut();
-
-        if let Some(Item::ArrayOfTables(array)) = doc.get_mut("package") {
-            array.retain(|pkg| {
-                pkg.get("name")
-                    .and_then(|v| v.as_str())
-                    .is_none_or(|name| !all_members.contains(name) || keep_members.contains(name))
-            });
+    let Some(lock_txt) = &recipe.skeleton.lock_file else {
+        return Ok(());
+    };
+
+    let doc: Document<String> = lock_txt.parse()?;
+    let mut doc = doc.into_mut();
+
+    let Some(Item::ArrayOfTables(array)) = doc.get_mut("package") else {
+        return Ok(());
+    };
+
+    let reachable = reachable_lock_packages(array, keep_members, keep_ws_deps);
+
+    let mut idx = 0;
+    array.retain(|_| {
+        let keep = reachable.contains(&idx);
+        idx += 1;
+        keep
+    });
+
+    recipe.skeleton.lock_file = Some(doc.to_string());
+
+    Ok(())
+}
+
+/// Compute the indices of the `[[package]]` entries reachable from the kept
+/// workspace members and workspace dependencies.
+///
+/// The traversal follows each package's `dependencies` strings through the
+/// lockfile's own dependency graph. The returned `reachable` set doubles as the
+/// visited set, so cyclic `dependencies` arrays terminate instead of looping.
+fn reachable_lock_packages(
+    array: &toml_edit::ArrayOfTables,
+    keep_members: &HashSet<String>,
+    keep_ws_deps: &HashSet<String>,
+) -> HashSet<usize> {
+    // Index every package by `(name, version)` and by name, so that dependency
+    // strings can be resolved even when they omit the version.
+    let packages: Vec<(String, Option<String>)> = array
+        .iter()
+        .map(|pkg| {
+            let name = pkg
+                .get("name")
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string();
+            let version = pkg
+                .get("version")
+                .and_then(|v| v.as_str())
+                .map(ToOwned::to_owned);
+            (name, version)
+        })
+        .collect();
+
+    let mut by_key: HashMap<(String, Option<String>), usize> = HashMap::new();
+    let mut by_name: HashMap<String, Vec<usize>> = HashMap::new();
+    for (idx, (name, version)) in packages.iter().enumerate() {
+        by_key.insert((name.clone(), version.clone()), idx);
+        by_name.entry(name.clone()).or_default().push(idx);
+    }
+
+    // Seed the worklist with the lock entries of the kept members and deps.
+    let mut stack: Vec<usize> = Vec::new();
+    let mut reachable: HashSet<usize> = HashSet::new();
+    for (idx, (name, _)) in packages.iter().enumerate() {
+        if (keep_members.contains(name) || keep_ws_deps.contains(name)) && reachable.insert(idx) {
+            stack.push(idx);
         }
+    }
 
-        recipe.skeleton.lock_file = Some(doc.to_string());
+    // Transitive traversal over each package's `dependencies` array.
+    while let Some(idx) = stack.pop() {
+        let Some(deps) = array
+            .get(idx)
+            .and_then(|pkg| pkg.get("dependencies"))
+            .and_then(Item::as_array)
+        else {
+            continue;
+        };
+        for dep in deps {
+            let Some(dep) = dep.as_str() else { continue };
+            for child in resolve_lock_dep(dep, &by_key, &by_name) {
+                if reachable.insert(child) {
+                    stack.push(child);
+                }
+            }
+        }
     }
 
-    Ok(())
+    reachable
+}
+
+/// Resolve a `Cargo.lock` dependency string of the form `"name"`,
+/// `"name version"` or `"name version (source)"` to the indices of the
+/// matching `[[package]]` entries.
+fn resolve_lock_dep(
+    dep: &str,
+    by_key: &HashMap<(String, Option<String>), usize>,
+    by_name: &HashMap<String, Vec<usize>>,
+) -> Vec<usize> {
+    let mut parts = dep.split_whitespace();
+    let Some(name) = parts.next() else {
+        return Vec::new();
+    };
+    // The version is the second token, unless that token is already the
+    // `(source)` suffix (which happens when the version is omitted).
+    let version = parts.next().filter(|tok| !tok.starts_with('('));
+
+    if let Some(version) = version
+        && let Some(&idx) = by_key.get(&(name.to_string(), Some(version.to_string())))
+    {
+        return vec![idx];
+    }
+
+    // No version (or no exact match): fall back to the name. A unique match is
+    // resolved directly; otherwise every same-name entry is kept to stay safe.
+    by_name.get(name).cloned().unwrap_or_default()
 }
 
 /// Extract the crate name from a manifest
@@ -350,7 +818,7 @@ mod tests {
         let want_path = "test-data/recipes/recipe-bar-reduced.json";
 
         let recipe = load_recipe(given_path)?;
-        let reduced = reduce_recipe(&recipe, "bar")?;
+        let reduced = reduce_recipe(&recipe, &["bar".to_string()], None)?;
 
         let want_reduced = load_recipe(want_path)?;
 
@@ -367,7 +835,7 @@ mod tests {
         let want_path = "test-data/recipes/recipe-foo-reduced.json";
 
         let recipe = load_recipe(given_path)?;
-        let reduced = reduce_recipe(&recipe, "foo")?;
+        let reduced = reduce_recipe(&recipe, &["foo".to_string()], None)?;
 
         let want_reduced = load_recipe(want_path)?;
 
@@ -377,4 +845,127 @@ mod tests {
         );
         Ok(())
     }
+
+    /// Build the `(name, version)` / name indices a `resolve_lock_dep` call expects.
+    fn index(
+        packages: &[(&str, &str)],
+    ) -> (
+        HashMap<(String, Option<String>), usize>,
+        HashMap<String, Vec<usize>>,
+    ) {
+        let mut by_key = HashMap::new();
+        let mut by_name: HashMap<String, Vec<usize>> = HashMap::new();
+        for (idx, (name, version)) in packages.iter().enumerate() {
+            by_key.insert((name.to_string(), Some(version.to_string())), idx);
+            by_name.entry(name.to_string()).or_default().push(idx);
+        }
+        (by_key, by_name)
+    }
+
+    #[test]
+    fn test_resolve_lock_dep_disambiguates_versions_and_source_suffix() {
+        let (by_key, by_name) = index(&[("serde", "1.0.0"), ("serde", "2.0.0")]);
+
+        // Duplicate crate names at different versions resolve by version.
+        assert_eq!(resolve_lock_dep("serde 2.0.0", &by_key, &by_name), vec![1]);
+
+        // A trailing `(source)` suffix must not be mistaken for the version.
+        assert_eq!(
+            resolve_lock_dep(
+                "serde 1.0.0 (registry+https://github.com/rust-lang/crates.io-index)",
+                &by_key,
+                &by_name,
+            ),
+            vec![0],
+        );
+
+        // A bare name that is ambiguous keeps every same-name entry.
+        let mut got = resolve_lock_dep("serde", &by_key, &by_name);
+        got.sort();
+        assert_eq!(got, vec![0, 1]);
+    }
+
+    #[test]
+    fn test_reachable_lock_packages_follows_closure_and_terminates_on_cycles() {
+        let lock = r#"
+[[package]]
+name = "bar"
+version = "0.1.0"
+dependencies = ["shared"]
+
+[[package]]
+name = "foo"
+version = "0.1.0"
+dependencies = ["only-foo"]
+
+[[package]]
+name = "shared"
+version = "1.0.0"
+dependencies = ["cyclic 2.0.0"]
+
+[[package]]
+name = "cyclic"
+version = "2.0.0"
+dependencies = ["shared 1.0.0"]
+
+[[package]]
+name = "only-foo"
+version = "3.0.0"
+"#;
+        let doc: Document<String> = lock.parse().unwrap();
+        let array = doc["package"].as_array_of_tables().unwrap();
+
+        let keep_members: HashSet<String> = ["bar".to_string()].into_iter().collect();
+        let reachable = reachable_lock_packages(array, &keep_members, &HashSet::new());
+
+        // bar -> shared -> cyclic -> shared (cycle terminates); foo/only-foo pruned.
+        assert_eq!(reachable, [0usize, 2, 3].into_iter().collect());
+    }
+
+    #[test]
+    fn test_collect_manifest_deps_resolves_renamed_package() {
+        let manifest = r#"
+[package]
+name = "app"
+
+[dependencies]
+myalias = { package = "bar", path = "../bar" }
+serde = { workspace = true }
+"#;
+        let doc: Document<String> = manifest.parse().unwrap();
+        let members: HashSet<String> = ["bar".to_string()].into_iter().collect();
+        let ws_deps: HashSet<String> = ["serde".to_string()].into_iter().collect();
+
+        let (found_members, found_ws_deps) = collect_manifest_deps(&doc, &members, &ws_deps);
+
+        // The alias `myalias` pulls in member `bar` via its `package` key.
+        assert_eq!(found_members, ["bar".to_string()].into_iter().collect());
+        // Members are mirrored into the ws-dep set so the closure reaches shared
+        // deps behind them, alongside the directly-named ws dep `serde`.
+        assert_eq!(
+            found_ws_deps,
+            ["bar".to_string(), "serde".to_string()].into_iter().collect()
+        );
+    }
+
+    #[test]
+    fn test_compute_transitive_deps_unions_multiple_targets() {
+        let mut graph: HashMap<String, HashSet<String>> = HashMap::new();
+        graph.insert("foo".to_string(), ["shared".to_string()].into_iter().collect());
+        graph.insert("bar".to_string(), ["baz".to_string()].into_iter().collect());
+        graph.insert("shared".to_string(), HashSet::new());
+        graph.insert("baz".to_string(), HashSet::new());
+        graph.insert("unrelated".to_string(), HashSet::new());
+
+        let keep = compute_transitive_deps(&["foo".to_string(), "bar".to_string()], &graph);
+
+        // The union of both targets' closures, and nothing else.
+        assert_eq!(
+            keep,
+            ["foo", "bar", "shared", "baz"]
+                .into_iter()
+                .map(ToOwned::to_owned)
+                .collect()
+        );
+    }
 }
@@ -1,6 +1,6 @@
 use anyhow::Result;
 use clap::Parser;
-use reduce_recipe::reduce_recipe_file;
+use reduce_recipe::{reduce_recipe_file, reduce_recipe_report_file};
 use std::path::PathBuf;
 
 /// Reduce a cargo chef workspace recipe by removing unused workspace members.
@@ -31,21 +31,52 @@ struct Args {
     )]
     recipe_out: PathBuf,
 
-    /// The workspace binary to keep.
-    /// All of its transitive workspace dependencies will be kept.
+    /// The workspace binaries to keep.
+    /// All of their transitive workspace dependencies will be kept.
+    ///
+    /// May be passed multiple times or as a comma-separated list to reduce to
+    /// the union of several members in one invocation.
     #[arg(
         long = "bin",
+        alias = "target-member",
         value_name = "NAME",
         required = true,
-        help = "The workspace binary to reduce to"
+        value_delimiter = ',',
+        help = "The workspace binaries to reduce to"
     )]
-    bin: String,
+    bin: Vec<String>,
+
+    /// Report what would be pruned instead of writing the reduced recipe.
+    #[arg(
+        long = "report",
+        help = "Print which members, workspace dependencies and lockfile packages would be pruned, without writing any output"
+    )]
+    report: bool,
+
+    /// Path to the real workspace root `Cargo.toml`.
+    ///
+    /// When set, the dependency graph is taken from `cargo metadata` against the
+    /// source tree instead of being reconstructed from the recipe's embedded
+    /// manifests, which makes reduction correct for feature-conditional deps.
+    #[arg(
+        long = "manifest-path",
+        value_name = "PATH",
+        help = "Use `cargo metadata` against this workspace Cargo.toml for an authoritative dependency graph"
+    )]
+    manifest_path: Option<PathBuf>,
 }
 
 fn main() -> Result<()> {
     let args = Args::parse();
 
-    reduce_recipe_file(&args.recipe_in, &args.recipe_out, &args.bin)?;
+    let manifest_path = args.manifest_path.as_deref();
+
+    if args.report {
+        let report = reduce_recipe_report_file(&args.recipe_in, &args.bin, manifest_path)?;
+        println!("{report}");
+    } else {
+        reduce_recipe_file(&args.recipe_in, &args.recipe_out, &args.bin, manifest_path)?;
+    }
 
     Ok(())
 }